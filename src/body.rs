@@ -0,0 +1,32 @@
+//! The single body type used for every request/response in the proxy.
+//!
+//! hyper 1.x no longer ships a concrete `Body`, so request and response
+//! handling is generic over `hyper::body::Body`. Boxing it once here keeps
+//! the rest of the proxy's signatures simple.
+
+use bytes::Bytes;
+use http_body_util::combinators::BoxBody;
+use http_body_util::{BodyExt, Empty, Full};
+
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+pub type ProxyBody = BoxBody<Bytes, BoxError>;
+
+/// Wraps a fully-buffered chunk as a `ProxyBody`.
+pub fn full<T: Into<Bytes>>(chunk: T) -> ProxyBody {
+    Full::new(chunk.into()).map_err(|never| match never {}).boxed()
+}
+
+/// An empty `ProxyBody`.
+pub fn empty() -> ProxyBody {
+    Empty::<Bytes>::new().map_err(|never| match never {}).boxed()
+}
+
+/// Boxes any hyper body (e.g. `hyper::body::Incoming`, or an already-mapped
+/// stream) into a `ProxyBody`.
+pub fn boxed<B>(body: B) -> ProxyBody
+where
+    B: hyper::body::Body<Data = Bytes> + Send + Sync + 'static,
+    B::Error: Into<BoxError>,
+{
+    body.map_err(Into::into).boxed()
+}
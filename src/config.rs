@@ -0,0 +1,147 @@
+//! Runtime configuration: an optional config file overlaid with environment
+//! variables (env always wins), so every listener and timing knob can be
+//! tuned without a recompile.
+//!
+//! The file format is deliberately plain: `key = value` lines, blank lines
+//! and `#` comments ignored, one file instead of pulling in a serde/toml
+//! dependency for a handful of scalars.
+
+use hyper::Uri;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+/// Fully resolved configuration for one run of the proxy.
+pub struct Config {
+    /// Main proxy listener. Required in practice (there's no proxy without
+    /// it), but modeled as optional so a deployment can disable it, e.g. to
+    /// run an admin-only instance.
+    pub proxy_addr: Option<SocketAddr>,
+    /// Metrics/admin listener (serves `/metrics`). Omit to disable.
+    pub admin_addr: Option<SocketAddr>,
+    /// Health-only listener, independent of the proxy listener. Omit to
+    /// disable; `/healthz` on the proxy listener keeps working either way.
+    pub health_addr: Option<SocketAddr>,
+
+    pub s3_base_uri: Uri,
+
+    pub request_timeout: Duration,
+    pub max_retries: u32,
+    pub retry_backoff_base_ms: u64,
+    pub graceful_shutdown_timeout: Duration,
+    pub max_buffered_body: usize,
+
+    /// Opt-in HTTP/3 transport to the upstream (requires the `http3` cargo
+    /// feature; a no-op env toggle otherwise). Buffered requests try HTTP/3
+    /// first and fall back to the HTTPS client on any connection error.
+    pub http3_enabled: bool,
+}
+
+const DEFAULT_PROXY_ADDR: &str = "0.0.0.0:8092";
+const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 30;
+const DEFAULT_MAX_RETRIES: u32 = 3;
+const DEFAULT_RETRY_BACKOFF_BASE_MS: u64 = 1000;
+const DEFAULT_GRACEFUL_SHUTDOWN_TIMEOUT_SECS: u64 = 30;
+const DEFAULT_MAX_BUFFERED_BODY: usize = 1024 * 1024;
+const DEFAULT_HTTP3_ENABLED: bool = false;
+
+impl Config {
+    /// Loads `CONFIG_FILE` (if set) and overlays environment variables on
+    /// top of it; environment variables win on conflicts.
+    pub fn load() -> Self {
+        let mut values = std::env::var("CONFIG_FILE")
+            .ok()
+            .map(|path| read_config_file(&path))
+            .unwrap_or_default();
+
+        for key in [
+            "PROXY_ADDR",
+            "ADMIN_ADDR",
+            "HEALTH_ADDR",
+            "S3_URL",
+            "REQUEST_TIMEOUT_SECS",
+            "MAX_RETRIES",
+            "RETRY_BACKOFF_BASE_MS",
+            "GRACEFUL_SHUTDOWN_TIMEOUT_SECS",
+            "MAX_BUFFERED_BODY",
+            "HTTP3_ENABLED",
+        ] {
+            if let Ok(value) = std::env::var(key) {
+                values.insert(key.to_string(), value);
+            }
+        }
+
+        let s3_url = values
+            .get("S3_URL")
+            .cloned()
+            .expect("S3_URL must be set (via CONFIG_FILE or the environment)");
+        let s3_base_uri = s3_url
+            .parse::<Uri>()
+            .unwrap_or_else(|e| panic!("Invalid S3_URL {:?}: {}", s3_url, e));
+
+        Self {
+            proxy_addr: parse_optional_addr(&values, "PROXY_ADDR", Some(DEFAULT_PROXY_ADDR)),
+            admin_addr: parse_optional_addr(&values, "ADMIN_ADDR", None),
+            health_addr: parse_optional_addr(&values, "HEALTH_ADDR", None),
+            s3_base_uri,
+            request_timeout: Duration::from_secs(parse_or(
+                &values,
+                "REQUEST_TIMEOUT_SECS",
+                DEFAULT_REQUEST_TIMEOUT_SECS,
+            )),
+            // At least one attempt is always made; `MAX_RETRIES=0` would
+            // otherwise skip the retry loop entirely and leave every
+            // buffered request unhandled.
+            max_retries: parse_or(&values, "MAX_RETRIES", DEFAULT_MAX_RETRIES).max(1),
+            retry_backoff_base_ms: parse_or(
+                &values,
+                "RETRY_BACKOFF_BASE_MS",
+                DEFAULT_RETRY_BACKOFF_BASE_MS,
+            ),
+            graceful_shutdown_timeout: Duration::from_secs(parse_or(
+                &values,
+                "GRACEFUL_SHUTDOWN_TIMEOUT_SECS",
+                DEFAULT_GRACEFUL_SHUTDOWN_TIMEOUT_SECS,
+            )),
+            max_buffered_body: parse_or(&values, "MAX_BUFFERED_BODY", DEFAULT_MAX_BUFFERED_BODY),
+            http3_enabled: parse_or(&values, "HTTP3_ENABLED", DEFAULT_HTTP3_ENABLED),
+        }
+    }
+}
+
+fn read_config_file(path: &str) -> HashMap<String, String> {
+    let contents = std::fs::read_to_string(path)
+        .unwrap_or_else(|e| panic!("Failed to read CONFIG_FILE at {}: {}", path, e));
+
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+        .collect()
+}
+
+/// Parses an address field that can be explicitly disabled with `none`.
+/// `default` is used only when the key is absent entirely.
+fn parse_optional_addr(
+    values: &HashMap<String, String>,
+    key: &str,
+    default: Option<&str>,
+) -> Option<SocketAddr> {
+    let raw = values.get(key).map(String::as_str).or(default)?;
+    if raw.eq_ignore_ascii_case("none") || raw.is_empty() {
+        return None;
+    }
+    Some(
+        raw.parse()
+            .unwrap_or_else(|e| panic!("Invalid socket address for {}: {:?}: {}", key, raw, e)),
+    )
+}
+
+fn parse_or<T: std::str::FromStr>(values: &HashMap<String, String>, key: &str, default: T) -> T {
+    values
+        .get(key)
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
@@ -0,0 +1,277 @@
+//! Experimental HTTP/3 (QUIC) transport for the upstream S3 connection.
+//!
+//! Gated behind the `http3` cargo feature and `Config::http3_enabled`
+//! (`HTTP3_ENABLED=true`). Maintains a small pool of H3 connections keyed by
+//! authority; `try_send` is the only entry point `proxy_handler` needs to
+//! know about, and returns `None` on any connection or request error so the
+//! caller falls back to the regular hyper-rustls client transparently.
+
+use crate::body::{self, ProxyBody};
+use crate::metrics::Metrics;
+use crate::AppState;
+use bytes::{Buf, Bytes};
+use h3::client::SendRequest;
+use h3_quinn::quinn;
+use hyper::{HeaderMap, Method, Response, Uri};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tokio::time::timeout;
+use tracing::{debug, warn};
+
+#[derive(Debug)]
+pub enum Http3Error {
+    Connect(String),
+    Request(String),
+}
+
+impl std::fmt::Display for Http3Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Http3Error::Connect(msg) => write!(f, "connect failed: {}", msg),
+            Http3Error::Request(msg) => write!(f, "request failed: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for Http3Error {}
+
+type H3Connection = SendRequest<h3_quinn::OpenStreams, Bytes>;
+
+/// A small pool of established H3 connections, keyed by the upstream URI's
+/// authority as written (`host` or `host:port`).
+///
+/// Connections aren't health-checked proactively: a broken one is simply
+/// dropped from the pool the next time a request over it fails, and a fresh
+/// one is dialed on the following attempt.
+pub struct Http3Pool {
+    endpoint: quinn::Endpoint,
+    connections: Mutex<HashMap<String, H3Connection>>,
+    /// One lock per authority currently being dialed, so a cold start or a
+    /// broken-connection retry only blocks callers for *that* authority
+    /// instead of serializing every request in the pool behind one
+    /// in-flight handshake.
+    dialing: Mutex<HashMap<String, Arc<Mutex<()>>>>,
+}
+
+impl Http3Pool {
+    /// Builds the pool's QUIC endpoint from the proxy's normal TLS trust
+    /// config, with ALPN pinned to `h3`.
+    pub fn new(tls_config: rustls::ClientConfig) -> Result<Self, Http3Error> {
+        let mut tls_config = tls_config;
+        tls_config.alpn_protocols = vec![b"h3".to_vec()];
+
+        let quic_crypto = quinn::crypto::rustls::QuicClientConfig::try_from(tls_config)
+            .map_err(|e| Http3Error::Connect(e.to_string()))?;
+        let client_config = quinn::ClientConfig::new(Arc::new(quic_crypto));
+
+        let mut endpoint = quinn::Endpoint::client("[::]:0".parse().unwrap())
+            .map_err(|e| Http3Error::Connect(e.to_string()))?;
+        endpoint.set_default_client_config(client_config);
+
+        Ok(Self {
+            endpoint,
+            connections: Mutex::new(HashMap::new()),
+            dialing: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Sends a buffered request over HTTP/3, reusing a pooled connection for
+    /// `authority` or dialing a new one.
+    async fn send(
+        &self,
+        authority: &str,
+        method: &Method,
+        uri: &Uri,
+        headers: &HeaderMap,
+        body: Bytes,
+    ) -> Result<Response<Bytes>, Http3Error> {
+        let send_request = match self.pooled_connection(authority).await {
+            Some(conn) => conn,
+            None => self.dial(authority).await?,
+        };
+
+        let result = issue_request(send_request, method, uri, headers, body).await;
+        if result.is_err() {
+            // The connection likely died mid-request; drop it so the next
+            // attempt for this authority dials fresh instead of reusing it.
+            self.connections.lock().await.remove(authority);
+        }
+        result
+    }
+
+    async fn pooled_connection(&self, authority: &str) -> Option<H3Connection> {
+        self.connections.lock().await.get(authority).cloned()
+    }
+
+    /// Dials a fresh connection for `authority`, without holding the shared
+    /// connection-map lock across the handshake. A per-authority lock still
+    /// prevents two concurrent callers from dialing the same authority
+    /// twice; callers for other authorities, or ones that find a pooled
+    /// connection, are never blocked by it.
+    async fn dial(&self, authority: &str) -> Result<H3Connection, Http3Error> {
+        let authority_lock = {
+            let mut dialing = self.dialing.lock().await;
+            Arc::clone(
+                dialing
+                    .entry(authority.to_string())
+                    .or_insert_with(|| Arc::new(Mutex::new(()))),
+            )
+        };
+        let _guard = authority_lock.lock().await;
+
+        // Another caller may have finished dialing while we waited for the
+        // per-authority lock.
+        if let Some(conn) = self.pooled_connection(authority).await {
+            return Ok(conn);
+        }
+
+        let conn = self.connect(authority).await?;
+        self.connections
+            .lock()
+            .await
+            .insert(authority.to_string(), conn.clone());
+        Ok(conn)
+    }
+
+    async fn connect(&self, authority: &str) -> Result<H3Connection, Http3Error> {
+        let (host, addr) = resolve_authority(authority)
+            .await
+            .map_err(|e| Http3Error::Connect(e.to_string()))?;
+
+        let connecting = self
+            .endpoint
+            .connect(addr, &host)
+            .map_err(|e| Http3Error::Connect(e.to_string()))?;
+        let quinn_conn = connecting
+            .await
+            .map_err(|e| Http3Error::Connect(e.to_string()))?;
+
+        let h3_conn = h3_quinn::Connection::new(quinn_conn);
+        let (mut driver, send_request) = h3::client::new(h3_conn)
+            .await
+            .map_err(|e| Http3Error::Connect(e.to_string()))?;
+
+        // The driver must be polled for the lifetime of the connection; it
+        // isn't awaited anywhere else, so give it its own task.
+        tokio::spawn(async move {
+            let e = driver.wait_idle().await;
+            debug!("HTTP/3 connection driver for a pooled connection exited: {}", e);
+        });
+
+        Ok(send_request)
+    }
+}
+
+async fn resolve_authority(authority: &str) -> std::io::Result<(String, SocketAddr)> {
+    let host = authority.split(':').next().unwrap_or(authority).to_string();
+    let lookup_target = if authority.contains(':') {
+        authority.to_string()
+    } else {
+        format!("{}:443", authority)
+    };
+    let addr = tokio::net::lookup_host(lookup_target)
+        .await?
+        .next()
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "no addresses resolved"))?;
+    Ok((host, addr))
+}
+
+async fn issue_request(
+    mut send_request: H3Connection,
+    method: &Method,
+    uri: &Uri,
+    headers: &HeaderMap,
+    body: Bytes,
+) -> Result<Response<Bytes>, Http3Error> {
+    let mut builder = hyper::Request::builder().method(method.clone()).uri(uri.clone());
+    for (name, value) in headers.iter() {
+        if name != hyper::header::HOST {
+            builder = builder.header(name, value);
+        }
+    }
+    let request = builder
+        .body(())
+        .map_err(|e| Http3Error::Request(e.to_string()))?;
+
+    let mut stream = send_request
+        .send_request(request)
+        .await
+        .map_err(|e| Http3Error::Request(e.to_string()))?;
+
+    if !body.is_empty() {
+        stream
+            .send_data(body)
+            .await
+            .map_err(|e| Http3Error::Request(e.to_string()))?;
+    }
+    stream
+        .finish()
+        .await
+        .map_err(|e| Http3Error::Request(e.to_string()))?;
+
+    let response = stream
+        .recv_response()
+        .await
+        .map_err(|e| Http3Error::Request(e.to_string()))?;
+
+    let mut collected = Vec::new();
+    while let Some(mut chunk) = stream
+        .recv_data()
+        .await
+        .map_err(|e| Http3Error::Request(e.to_string()))?
+    {
+        collected.extend_from_slice(chunk.copy_to_bytes(chunk.remaining()).as_ref());
+    }
+
+    let (parts, ()) = response.into_parts();
+    Ok(Response::from_parts(parts, Bytes::from(collected)))
+}
+
+/// Attempts a buffered request over HTTP/3, returning `None` (so the caller
+/// falls back to the regular HTTPS client) on any connection, request, or
+/// missing-authority error, or if the attempt doesn't finish within
+/// `state.config.request_timeout` (the same timeout the HTTPS paths use).
+pub async fn try_send(
+    state: &AppState,
+    method: &Method,
+    uri: &Uri,
+    headers: &HeaderMap,
+    body: &Bytes,
+) -> Option<Response<ProxyBody>> {
+    let authority = uri.authority()?.as_str().to_string();
+    let pool = state.http3_pool.as_ref()?;
+
+    let start = std::time::Instant::now();
+    let attempt = timeout(
+        state.config.request_timeout,
+        pool.send(&authority, method, uri, headers, body.clone()),
+    )
+    .await;
+    match attempt {
+        Ok(Ok(resp)) => {
+            state
+                .metrics
+                .observe_upstream_latency(method, start.elapsed().as_secs_f64());
+            let (parts, bytes) = resp.into_parts();
+            let response_body =
+                Metrics::counted_body(body::full(bytes), state.metrics.bytes_out_counter());
+            Some(Response::from_parts(parts, response_body))
+        }
+        Ok(Err(e)) => {
+            warn!(
+                "HTTP/3 request to {} failed ({}), falling back to HTTPS",
+                authority, e
+            );
+            None
+        }
+        Err(_) => {
+            warn!(
+                "HTTP/3 request to {} timed out, falling back to HTTPS",
+                authority
+            );
+            None
+        }
+    }
+}
@@ -1,40 +1,63 @@
-use hyper::body::to_bytes;
-use hyper::{
-    service::{make_service_fn, service_fn},
-    Body, Client, Request, Response, Server, Uri, StatusCode,
-};
-use hyper_rustls::HttpsConnectorBuilder;
-use rustls::{client::{ServerCertVerifier, ServerCertVerified}, ServerName};
+use crate::body::ProxyBody;
+use bytes::Bytes;
+use http_body_util::BodyExt;
+use hyper::body::Incoming;
+use hyper::service::service_fn;
+use hyper::{Method, Request, Response, StatusCode, Uri};
+use hyper_rustls::HttpsConnector;
+use hyper_util::client::legacy::connect::HttpConnector;
+use hyper_util::client::legacy::Client;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use hyper_util::server::conn::auto;
+use hyper_util::server::graceful::GracefulShutdown;
+use rand::Rng;
 use std::convert::Infallible;
-use std::env;
 use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant, SystemTime};
+use tokio::net::TcpListener;
+use tokio::sync::watch;
 use tokio::time::timeout;
 use tracing::{debug, error, info, instrument, warn};
-use tracing_subscriber::{FmtSubscriber, EnvFilter};
-use rand::Rng;
-use tokio::sync::oneshot;
-use std::sync::atomic::{AtomicBool, Ordering};
+use tracing_subscriber::{EnvFilter, FmtSubscriber};
+
+mod body;
+mod config;
+#[cfg(feature = "http3")]
+mod http3;
+mod metrics;
+mod signing;
+mod tls;
+mod validation;
+
+use config::Config;
+use metrics::Metrics;
+use signing::SigningConfig;
+use validation::{ValidationConfig, ValidationError};
+
+type HttpsClient = Client<HttpsConnector<HttpConnector>, ProxyBody>;
+
+/// Everything a request handler needs, shared across every listener.
+struct AppState {
+    config: Config,
+    client: HttpsClient,
+    signing_config: Option<SigningConfig>,
+    validation_config: ValidationConfig,
+    metrics: Metrics,
+    stopping: AtomicBool,
+    /// `Some` only when built with the `http3` feature and `HTTP3_ENABLED=true`.
+    #[cfg(feature = "http3")]
+    http3_pool: Option<http3::Http3Pool>,
+}
 
-const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
-const MAX_RETRIES: u32 = 3;
-const GRACEFUL_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
-
-struct NoVerifier;
-
-impl ServerCertVerifier for NoVerifier {
-    fn verify_server_cert(
-        &self,
-        _end_entity: &rustls::Certificate,
-        _intermediates: &[rustls::Certificate],
-        _server_name: &ServerName,
-        _scts: &mut dyn Iterator<Item = &[u8]>,
-        _ocsp_response: &[u8],
-        _now: SystemTime,
-    ) -> Result<ServerCertVerified, rustls::Error> {
-        Ok(ServerCertVerified::assertion())
-    }
+/// The outbound request body, chosen once per request based on its size.
+///
+/// A buffered body can be cloned and resent on retry; a streamed body is
+/// moved straight into the single outbound request and cannot be retried.
+enum OutboundBody {
+    Buffered(Bytes),
+    Streamed(ProxyBody),
 }
 
 #[tokio::main]
@@ -45,7 +68,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             EnvFilter::from_default_env()
                 .add_directive("s3_proxy=debug".parse().unwrap())
                 .add_directive("hyper=debug".parse().unwrap())
-                .add_directive("hyper_rustls=debug".parse().unwrap())
+                .add_directive("hyper_rustls=debug".parse().unwrap()),
         )
         .finish();
 
@@ -54,119 +77,277 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     info!("Starting s3-proxy");
 
-    let s3_url = env::var("S3_URL").expect("S3_URL environment variable not set");
-    info!("S3_URL set to {}", s3_url);
+    let config = Config::load();
+    info!("S3_URL set to {}", config.s3_base_uri);
 
-    let s3_base_uri = s3_url.parse::<Uri>().map_err(|e| {
-        error!("Invalid S3_URL: {}", e);
-        e
-    })?;
-
-    let addr: SocketAddr = ([0, 0, 0, 0], 8092).into();
-
-    // Create HTTPS client with certificate verification disabled (for testing only)
-    let https = HttpsConnectorBuilder::new()
-        .with_tls_config(
-            rustls::ClientConfig::builder()
-                .with_safe_defaults()
-                .with_custom_certificate_verifier(Arc::new(NoVerifier))
-                .with_no_client_auth()
-        )
+    let https = hyper_rustls::HttpsConnectorBuilder::new()
+        .with_tls_config(tls::build_client_config())
         .https_only()
         .enable_http1()
         .build();
 
-    let client = Arc::new(Client::builder().build::<_, hyper::Body>(https));
-
-    let (tx, rx) = oneshot::channel::<()>();
-    let stopping = Arc::new(AtomicBool::new(false));
-
-    let stopping_clone = stopping.clone();
-    let make_svc = make_service_fn(move |conn: &hyper::server::conn::AddrStream| {
-        let s3_base_uri = s3_base_uri.clone();
-        let remote_addr = conn.remote_addr();
-        let client = Arc::clone(&client);
-        let stopping = stopping_clone.clone();
-        async move {
-            Ok::<_, Infallible>(service_fn(move |req| {
-                handle_request(req, s3_base_uri.clone(), remote_addr, Arc::clone(&client), stopping.clone())
-            }))
+    let client: HttpsClient = Client::builder(TokioExecutor::new()).build(https);
+
+    #[cfg(not(feature = "http3"))]
+    if config.http3_enabled {
+        warn!(
+            "HTTP3_ENABLED=true but this binary was not built with the `http3` cargo feature; \
+             staying on HTTPS"
+        );
+    }
+
+    #[cfg(feature = "http3")]
+    let http3_pool = if config.http3_enabled {
+        match http3::Http3Pool::new(tls::build_client_config()) {
+            Ok(pool) => {
+                info!("HTTP/3 transport enabled for the upstream S3 connection");
+                Some(pool)
+            }
+            Err(e) => {
+                warn!("Failed to initialize HTTP/3 pool ({}), staying on HTTPS", e);
+                None
+            }
         }
-    });
+    } else {
+        None
+    };
 
-    let server = Server::bind(&addr).serve(make_svc);
+    let signing_config = match SigningConfig::from_env() {
+        Some(config) => {
+            info!("AWS credentials found, outbound requests will be signed with SigV4");
+            Some(config)
+        }
+        None => {
+            warn!("No AWS credentials configured, outbound requests will not be signed");
+            None
+        }
+    };
 
-    let graceful = server.with_graceful_shutdown(async {
-        rx.await.ok();
+    let validation_config = ValidationConfig::from_env();
+
+    let state = Arc::new(AppState {
+        config,
+        client,
+        signing_config,
+        validation_config,
+        metrics: Metrics::new(),
+        stopping: AtomicBool::new(false),
+        #[cfg(feature = "http3")]
+        http3_pool,
     });
 
-    info!("Listening on http://{}", addr);
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let mut listener_tasks = Vec::new();
 
-    tokio::select! {
-        result = graceful => {
-            if let Err(e) = result {
-                error!("Server error: {}", e);
+    if let Some(addr) = state.config.proxy_addr {
+        info!("Listening (proxy) on http://{}", addr);
+        listener_tasks.push(spawn_proxy_listener(addr, Arc::clone(&state), shutdown_rx.clone()));
+    }
+    if let Some(addr) = state.config.health_addr {
+        info!("Listening (health) on http://{}", addr);
+        listener_tasks.push(spawn_health_listener(addr, shutdown_rx.clone()));
+    }
+    if let Some(addr) = state.config.admin_addr {
+        info!("Listening (admin) on http://{}", addr);
+        listener_tasks.push(spawn_admin_listener(addr, Arc::clone(&state), shutdown_rx.clone()));
+    }
+
+    if listener_tasks.is_empty() {
+        warn!("No listeners configured (PROXY_ADDR/HEALTH_ADDR/ADMIN_ADDR all disabled), exiting");
+        return Ok(());
+    }
+
+    tokio::signal::ctrl_c().await.ok();
+    info!("Received shutdown signal, initiating graceful shutdown");
+    state.stopping.store(true, Ordering::SeqCst);
+    shutdown_tx.send(true).ok();
+
+    let wait_for_all = async {
+        for task in listener_tasks {
+            if let Err(e) = task.await {
+                error!("Listener task panicked: {:?}", e);
             }
         }
-        _ = tokio::signal::ctrl_c() => {
-            info!("Received shutdown signal");
-        }
+    };
+    if timeout(state.config.graceful_shutdown_timeout, wait_for_all)
+        .await
+        .is_err()
+    {
+        warn!(
+            "Graceful shutdown timed out after {:?}, exiting anyway",
+            state.config.graceful_shutdown_timeout
+        );
     }
 
-    info!("Initiating graceful shutdown");
-    stopping.store(true, Ordering::SeqCst);
-    tx.send(()).ok();
-
-    // Wait for the server to finish processing ongoing requests
-    tokio::time::sleep(GRACEFUL_SHUTDOWN_TIMEOUT).await;
     info!("Server shut down");
 
     Ok(())
 }
 
-#[instrument(skip(req, client, stopping))]
+/// Accepts connections on `addr` until the shutdown channel fires, serving
+/// each with `make_service`, and drains in-flight connections before
+/// returning.
+async fn serve<S>(
+    addr: SocketAddr,
+    mut shutdown_rx: watch::Receiver<bool>,
+    make_service: impl Fn(SocketAddr) -> S,
+) where
+    S: hyper::service::Service<Request<Incoming>, Response = Response<ProxyBody>, Error = Infallible>
+        + Send
+        + 'static,
+    S::Future: Send,
+{
+    let listener = match TcpListener::bind(addr).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            error!("Failed to bind listener on {}: {}", addr, e);
+            return;
+        }
+    };
+
+    let builder = auto::Builder::new(TokioExecutor::new());
+    let graceful = GracefulShutdown::new();
+
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let (stream, remote_addr) = match accepted {
+                    Ok(pair) => pair,
+                    Err(e) => {
+                        warn!("Failed to accept connection: {}", e);
+                        continue;
+                    }
+                };
+                let io = TokioIo::new(stream);
+                let service = make_service(remote_addr);
+                let builder = builder.clone();
+                let watcher = graceful.watcher();
+                // The connection is built inside the spawned task (rather
+                // than passed in already-constructed) so it can borrow its
+                // own `builder` clone instead of the shared one, which the
+                // task would otherwise have to outlive.
+                tokio::spawn(async move {
+                    let conn = builder.serve_connection(io, service);
+                    if let Err(e) = watcher.watch(conn).await {
+                        debug!("Connection error: {:?}", e);
+                    }
+                });
+            }
+            _ = shutdown_rx.changed() => {
+                if *shutdown_rx.borrow() {
+                    break;
+                }
+            }
+        }
+    }
+
+    graceful.shutdown().await;
+}
+
+fn spawn_proxy_listener(
+    addr: SocketAddr,
+    state: Arc<AppState>,
+    shutdown_rx: watch::Receiver<bool>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        serve(addr, shutdown_rx, move |remote_addr| {
+            let state = Arc::clone(&state);
+            service_fn(move |req| handle_request(req, remote_addr, Arc::clone(&state)))
+        })
+        .await;
+    })
+}
+
+fn spawn_health_listener(
+    addr: SocketAddr,
+    shutdown_rx: watch::Receiver<bool>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        serve(addr, shutdown_rx, |_remote_addr| {
+            service_fn(|_req: Request<Incoming>| async move {
+                Ok::<_, Infallible>(Response::new(body::full("OK")))
+            })
+        })
+        .await;
+    })
+}
+
+fn spawn_admin_listener(
+    addr: SocketAddr,
+    state: Arc<AppState>,
+    shutdown_rx: watch::Receiver<bool>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        serve(addr, shutdown_rx, move |_remote_addr| {
+            let state = Arc::clone(&state);
+            service_fn(move |req: Request<Incoming>| {
+                let state = Arc::clone(&state);
+                async move {
+                    let response = match (req.method(), req.uri().path()) {
+                        (&Method::GET, "/healthz") => Response::new(body::full("OK")),
+                        (&Method::GET, "/metrics") => Response::builder()
+                            .status(StatusCode::OK)
+                            .header("content-type", "text/plain; version=0.0.4")
+                            .body(body::full(state.metrics.encode()))
+                            .unwrap(),
+                        _ => Response::builder()
+                            .status(StatusCode::NOT_FOUND)
+                            .body(body::empty())
+                            .unwrap(),
+                    };
+                    Ok::<_, Infallible>(response)
+                }
+            })
+        })
+        .await;
+    })
+}
+
+#[instrument(skip(req, state))]
 async fn handle_request(
-    req: Request<Body>,
-    s3_base_uri: Uri,
+    req: Request<Incoming>,
     remote_addr: SocketAddr,
-    client: Arc<Client<hyper_rustls::HttpsConnector<hyper::client::HttpConnector>>>,
-    stopping: Arc<AtomicBool>,
-) -> Result<Response<Body>, hyper::Error> {
-    if stopping.load(Ordering::SeqCst) {
+    state: Arc<AppState>,
+) -> Result<Response<ProxyBody>, Infallible> {
+    if state.stopping.load(Ordering::SeqCst) {
         return Ok(Response::builder()
             .status(StatusCode::SERVICE_UNAVAILABLE)
-            .body(Body::from("Server is shutting down"))
+            .body(body::full("Server is shutting down"))
             .unwrap());
     }
 
     if req.uri().path() == "/healthz" {
-        return Ok(Response::new(Body::from("OK")));
+        return Ok(Response::new(body::full("OK")));
     }
 
+    let method = req.method().clone();
+    let _in_flight = state.metrics.in_flight_guard();
     let start = Instant::now();
 
-    match proxy_handler(req, s3_base_uri, remote_addr, client).await {
+    let response = match proxy_handler(req, remote_addr, &state).await {
         Ok(response) => {
             let duration = start.elapsed();
             debug!("Request duration: {:?}", duration);
-            Ok(response)
+            response
         }
         Err(e) => {
             error!("Error handling request: {:?}", e);
-            Ok(Response::builder()
+            Response::builder()
                 .status(StatusCode::INTERNAL_SERVER_ERROR)
-                .body(Body::from("Internal Server Error"))
-                .unwrap())
+                .body(body::full("Internal Server Error"))
+                .unwrap()
         }
-    }
+    };
+
+    state.metrics.record_request(&method, response.status());
+    Ok(response)
 }
 
 async fn proxy_handler(
-    req: Request<Body>,
-    s3_base_uri: Uri,
+    req: Request<Incoming>,
     remote_addr: SocketAddr,
-    client: Arc<Client<hyper_rustls::HttpsConnector<hyper::client::HttpConnector>>>,
-) -> Result<Response<Body>, hyper::Error> {
+    state: &AppState,
+) -> Result<Response<ProxyBody>, hyper::Error> {
     info!(
         "Received request from {}: {} {}",
         remote_addr,
@@ -174,86 +355,200 @@ async fn proxy_handler(
         req.uri()
     );
 
-    if !is_valid_s3_request(&req) {
-        warn!("Invalid S3 request received");
+    if let Err(e) = validation::validate(&req, &state.validation_config) {
+        warn!("Rejected request from {}: {:?}", remote_addr, e);
+        let status = match e {
+            ValidationError::BucketNotAllowed => StatusCode::FORBIDDEN,
+            ValidationError::MalformedPath | ValidationError::MethodNotAllowed => {
+                StatusCode::BAD_REQUEST
+            }
+        };
         return Ok(Response::builder()
-            .status(hyper::StatusCode::BAD_REQUEST)
-            .body(Body::from("Invalid S3 request"))
+            .status(status)
+            .body(body::full("Invalid S3 request"))
             .unwrap());
     }
 
-    let uri = match construct_uri(&s3_base_uri, req.uri()) {
+    let uri = match construct_uri(&state.config.s3_base_uri, req.uri()) {
         Ok(uri) => uri,
         Err(e) => {
             error!("Failed to construct URI: {}", e);
             return Ok(Response::builder()
-                .status(hyper::StatusCode::INTERNAL_SERVER_ERROR)
-                .body(Body::from("Internal Server Error"))
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .body(body::full("Internal Server Error"))
                 .unwrap());
         }
     };
 
     let method = req.method().clone();
     let headers = req.headers().clone();
-    let body_bytes = to_bytes(req.into_body()).await?;
 
-    for retry in 0..MAX_RETRIES {
-        let mut new_req = Request::builder()
-            .method(method.clone())
-            .uri(uri.clone());
+    let content_length = headers
+        .get(hyper::header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<usize>().ok());
+    let max_buffered = state.config.max_buffered_body;
+
+    let (outbound_body, max_attempts) = match content_length {
+        Some(len) if len <= max_buffered => {
+            let body_bytes = req.into_body().collect().await?.to_bytes();
+            state.metrics.add_bytes_in(body_bytes.len() as u64);
+            (OutboundBody::Buffered(body_bytes), state.config.max_retries)
+        }
+        None if method == Method::GET || method == Method::HEAD => {
+            (OutboundBody::Buffered(Bytes::new()), state.config.max_retries)
+        }
+        other => {
+            debug!(
+                "Streaming request body without retry (content-length: {:?}, limit: {})",
+                other, max_buffered
+            );
+            let counted =
+                Metrics::counted_body(body::boxed(req.into_body()), state.metrics.bytes_in_counter());
+            (OutboundBody::Streamed(counted), 1)
+        }
+    };
+
+    // The signature covers the body and headers, neither of which change
+    // across retries, so it is computed once up front rather than per attempt.
+    let host = uri.authority().map(|a| a.as_str()).unwrap_or_default().to_string();
+    let content_sha256 = match &outbound_body {
+        OutboundBody::Buffered(bytes) => signing::sha256_hex(bytes),
+        OutboundBody::Streamed(_) => signing::UNSIGNED_PAYLOAD.to_string(),
+    };
+    let signed_headers = state.signing_config.as_ref().map(|config| {
+        signing::sign(
+            config,
+            &method,
+            &uri,
+            &headers,
+            &host,
+            &content_sha256,
+            SystemTime::now(),
+        )
+    });
 
+    let build_request = |body: ProxyBody, signed_headers: &Option<signing::SignedHeaders>| {
+        let mut new_req = Request::builder().method(method.clone()).uri(uri.clone());
         for (name, value) in headers.iter() {
-            if name != "host" {
+            if should_forward_header(name.as_str(), signed_headers.is_some()) {
                 new_req = new_req.header(name, value);
             }
         }
+        if let Some(signed) = signed_headers {
+            new_req = new_req
+                .header("x-amz-date", signed.x_amz_date.clone())
+                .header("x-amz-content-sha256", signed.x_amz_content_sha256.clone())
+                .header("authorization", signed.authorization.clone());
+            if let Some(token) = &signed.x_amz_security_token {
+                new_req = new_req.header("x-amz-security-token", token.clone());
+            }
+        }
+        new_req.body(body).expect("Failed to build request")
+    };
 
-        let new_req = new_req.body(Body::from(body_bytes.clone())).expect("Failed to build request");
-
-        debug!("Sending request to S3: {:?}", new_req);
-        match timeout(REQUEST_TIMEOUT, client.request(new_req)).await {
-            Ok(Ok(resp)) => {
-                let status = resp.status();
-                info!("Response from S3: status {}", status);
-                debug!("Response headers: {:?}", resp.headers());
-                
-                // Read the entire response body
-                let body_bytes = hyper::body::to_bytes(resp.into_body()).await?;
-                
-                // Create a new response with the read body
-                return Ok(Response::builder()
-                    .status(status)
-                    .body(Body::from(body_bytes))
-                    .unwrap());
+    match outbound_body {
+        OutboundBody::Buffered(body_bytes) => {
+            #[cfg(feature = "http3")]
+            if state.config.http3_enabled {
+                // Built only to get the same signed headers the HTTPS path
+                // would send; its (empty) body is discarded.
+                let probe_req = build_request(body::empty(), &signed_headers);
+                if let Some(resp) = http3::try_send(
+                    state,
+                    probe_req.method(),
+                    probe_req.uri(),
+                    probe_req.headers(),
+                    &body_bytes,
+                )
+                .await
+                {
+                    return Ok(resp);
+                }
             }
-            Ok(Err(e)) => {
-                error!(
-                    "Error forwarding request to S3: {}, retry: {}",
-                    e, retry
-                );
-                if retry < MAX_RETRIES - 1 {
-                    let backoff = 2u64.pow(retry) * 1000 + rand::thread_rng().gen_range(0..1000);
-                    tokio::time::sleep(Duration::from_millis(backoff)).await;
-                } else {
-                    return Ok(Response::builder()
-                        .status(hyper::StatusCode::BAD_GATEWAY)
-                        .body(Body::from("Bad Gateway"))
-                        .unwrap());
+
+            for retry in 0..max_attempts {
+                let new_req = build_request(body::full(body_bytes.clone()), &signed_headers);
+
+                debug!("Sending request to S3: {:?}", new_req);
+                let upstream_start = Instant::now();
+                match timeout(state.config.request_timeout, state.client.request(new_req)).await {
+                    Ok(Ok(resp)) => {
+                        state
+                            .metrics
+                            .observe_upstream_latency(&method, upstream_start.elapsed().as_secs_f64());
+                        return Ok(forward_response(resp, &state.metrics));
+                    }
+                    Ok(Err(e)) => {
+                        error!("Error forwarding request to S3: {}, retry: {}", e, retry);
+                        if retry < max_attempts - 1 {
+                            state.metrics.inc_retries(&method);
+                            let backoff = 2u64.pow(retry) * state.config.retry_backoff_base_ms
+                                + rand::thread_rng().gen_range(0..state.config.retry_backoff_base_ms);
+                            tokio::time::sleep(Duration::from_millis(backoff)).await;
+                        } else {
+                            return Ok(Response::builder()
+                                .status(StatusCode::BAD_GATEWAY)
+                                .body(body::full("Bad Gateway"))
+                                .unwrap());
+                        }
+                    }
+                    Err(_) => {
+                        warn!("Request to S3 timed out, retry: {}", retry);
+                        state.metrics.inc_timeouts(&method);
+                        if retry == max_attempts - 1 {
+                            return Ok(Response::builder()
+                                .status(StatusCode::GATEWAY_TIMEOUT)
+                                .body(body::full("Gateway Timeout"))
+                                .unwrap());
+                        }
+                        state.metrics.inc_retries(&method);
+                    }
                 }
             }
-            Err(_) => {
-                warn!("Request to S3 timed out, retry: {}", retry);
-                if retry == MAX_RETRIES - 1 {
-                    return Ok(Response::builder()
-                        .status(hyper::StatusCode::GATEWAY_TIMEOUT)
-                        .body(Body::from("Gateway Timeout"))
-                        .unwrap());
+            unreachable!()
+        }
+        OutboundBody::Streamed(body) => {
+            let new_req = build_request(body, &signed_headers);
+
+            debug!("Sending request to S3: {:?}", new_req);
+            let upstream_start = Instant::now();
+            match timeout(state.config.request_timeout, state.client.request(new_req)).await {
+                Ok(Ok(resp)) => {
+                    state
+                        .metrics
+                        .observe_upstream_latency(&method, upstream_start.elapsed().as_secs_f64());
+                    Ok(forward_response(resp, &state.metrics))
+                }
+                Ok(Err(e)) => {
+                    error!("Error forwarding streamed request to S3: {} (not retryable)", e);
+                    Ok(Response::builder()
+                        .status(StatusCode::BAD_GATEWAY)
+                        .body(body::full("Bad Gateway"))
+                        .unwrap())
+                }
+                Err(_) => {
+                    warn!("Streamed request to S3 timed out (not retryable)");
+                    state.metrics.inc_timeouts(&method);
+                    Ok(Response::builder()
+                        .status(StatusCode::GATEWAY_TIMEOUT)
+                        .body(body::full("Gateway Timeout"))
+                        .unwrap())
                 }
             }
         }
     }
+}
+
+/// Forwards an upstream response to the client without buffering its body,
+/// counting bytes as they stream through.
+fn forward_response(resp: Response<Incoming>, metrics: &Metrics) -> Response<ProxyBody> {
+    let status = resp.status();
+    info!("Response from S3: status {}", status);
+    debug!("Response headers: {:?}", resp.headers());
 
-    unreachable!()
+    let body = Metrics::counted_body(body::boxed(resp.into_body()), metrics.bytes_out_counter());
+    Response::builder().status(status).body(body).unwrap()
 }
 
 fn construct_uri(base_uri: &Uri, request_uri: &Uri) -> Result<Uri, hyper::http::Error> {
@@ -261,12 +556,60 @@ fn construct_uri(base_uri: &Uri, request_uri: &Uri) -> Result<Uri, hyper::http::
     let path = request_uri.path();
     let query = request_uri.query().map(|q| format!("?{}", q)).unwrap_or_default();
     parts.path_and_query = Some(format!("{}{}", path, query).parse().unwrap());
-    Uri::from_parts(parts).map_err(|e| hyper::http::Error::from(e))
+    Uri::from_parts(parts).map_err(hyper::http::Error::from)
 }
 
-fn is_valid_s3_request(_req: &Request<Body>) -> bool {
-    // Implement your S3 request validation logic here
-    // For example, check if the path starts with a valid bucket name
-    // or if the request contains required S3 headers
-    true // Placeholder
+/// Whether a header from the incoming client request should be copied onto
+/// the outbound (possibly re-signed) request.
+///
+/// `host` is always dropped; it's re-added from the upstream authority.
+/// When `will_sign` is true, the SigV4 headers are dropped too, since
+/// `signing::sign` recomputes and re-adds them and `http::request::Builder::header`
+/// appends rather than replaces — forwarding the client's originals
+/// alongside the computed ones would leak the client's `Authorization`
+/// header upstream and desync the signature from what's actually sent.
+fn should_forward_header(name: &str, will_sign: bool) -> bool {
+    let is_signed_header = matches!(
+        name,
+        "authorization" | "x-amz-date" | "x-amz-content-sha256" | "x-amz-security-token"
+    );
+    name != "host" && !(will_sign && is_signed_header)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_forward_header_drops_host_regardless_of_signing() {
+        assert!(!should_forward_header("host", false));
+        assert!(!should_forward_header("host", true));
+    }
+
+    #[test]
+    fn should_forward_header_drops_client_supplied_sigv4_headers_only_when_resigning() {
+        for name in [
+            "authorization",
+            "x-amz-date",
+            "x-amz-content-sha256",
+            "x-amz-security-token",
+        ] {
+            assert!(
+                !should_forward_header(name, true),
+                "{} must not be forwarded alongside the recomputed value",
+                name
+            );
+            assert!(
+                should_forward_header(name, false),
+                "{} should pass through unchanged when signing is disabled",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn should_forward_header_keeps_unrelated_headers() {
+        assert!(should_forward_header("x-amz-meta-tag", true));
+        assert!(should_forward_header("content-type", false));
+    }
 }
@@ -0,0 +1,181 @@
+//! Prometheus metrics for the proxy: request counts by method/status,
+//! upstream latency, retry and timeout counts, bytes proxied, and an
+//! in-flight request gauge. Served as Prometheus text format from the
+//! admin listener's `/metrics` route.
+
+use crate::body::ProxyBody;
+use hyper::{Method, StatusCode};
+use prometheus::{
+    Encoder, HistogramVec, IntCounter, IntCounterVec, IntGauge, Opts, Registry, TextEncoder,
+};
+
+pub struct Metrics {
+    registry: Registry,
+    requests_total: IntCounterVec,
+    upstream_latency_seconds: HistogramVec,
+    retries_total: IntCounterVec,
+    timeouts_total: IntCounterVec,
+    bytes_in_total: IntCounter,
+    bytes_out_total: IntCounter,
+    in_flight_requests: IntGauge,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let registry = Registry::new();
+
+        let requests_total = IntCounterVec::new(
+            Opts::new("s3_proxy_requests_total", "Requests handled, by method and response status"),
+            &["method", "status"],
+        )
+        .expect("metric definition is valid");
+
+        let upstream_latency_seconds = HistogramVec::new(
+            prometheus::HistogramOpts::new(
+                "s3_proxy_upstream_latency_seconds",
+                "Latency of requests forwarded to the upstream S3 backend",
+            ),
+            &["method"],
+        )
+        .expect("metric definition is valid");
+
+        let retries_total = IntCounterVec::new(
+            Opts::new("s3_proxy_retries_total", "Retries issued against the upstream, by method"),
+            &["method"],
+        )
+        .expect("metric definition is valid");
+
+        let timeouts_total = IntCounterVec::new(
+            Opts::new("s3_proxy_timeouts_total", "Upstream request timeouts, by method"),
+            &["method"],
+        )
+        .expect("metric definition is valid");
+
+        let bytes_in_total = IntCounter::new(
+            "s3_proxy_bytes_in_total",
+            "Bytes received from clients and forwarded upstream",
+        )
+        .expect("metric definition is valid");
+
+        let bytes_out_total = IntCounter::new(
+            "s3_proxy_bytes_out_total",
+            "Bytes received from upstream and forwarded to clients",
+        )
+        .expect("metric definition is valid");
+
+        let in_flight_requests = IntGauge::new(
+            "s3_proxy_in_flight_requests",
+            "Requests currently being handled",
+        )
+        .expect("metric definition is valid");
+
+        registry
+            .register(Box::new(requests_total.clone()))
+            .expect("metric registration is unique");
+        registry
+            .register(Box::new(upstream_latency_seconds.clone()))
+            .expect("metric registration is unique");
+        registry
+            .register(Box::new(retries_total.clone()))
+            .expect("metric registration is unique");
+        registry
+            .register(Box::new(timeouts_total.clone()))
+            .expect("metric registration is unique");
+        registry
+            .register(Box::new(bytes_in_total.clone()))
+            .expect("metric registration is unique");
+        registry
+            .register(Box::new(bytes_out_total.clone()))
+            .expect("metric registration is unique");
+        registry
+            .register(Box::new(in_flight_requests.clone()))
+            .expect("metric registration is unique");
+
+        Self {
+            registry,
+            requests_total,
+            upstream_latency_seconds,
+            retries_total,
+            timeouts_total,
+            bytes_in_total,
+            bytes_out_total,
+            in_flight_requests,
+        }
+    }
+
+    pub fn record_request(&self, method: &Method, status: StatusCode) {
+        self.requests_total
+            .with_label_values(&[method.as_str(), status.as_str()])
+            .inc();
+    }
+
+    pub fn observe_upstream_latency(&self, method: &Method, seconds: f64) {
+        self.upstream_latency_seconds
+            .with_label_values(&[method.as_str()])
+            .observe(seconds);
+    }
+
+    pub fn inc_retries(&self, method: &Method) {
+        self.retries_total.with_label_values(&[method.as_str()]).inc();
+    }
+
+    pub fn inc_timeouts(&self, method: &Method) {
+        self.timeouts_total.with_label_values(&[method.as_str()]).inc();
+    }
+
+    pub fn add_bytes_in(&self, n: u64) {
+        self.bytes_in_total.inc_by(n);
+    }
+
+    /// Increments the in-flight gauge and returns a guard that decrements it
+    /// on drop, so it stays accurate regardless of which return path a
+    /// request takes.
+    pub fn in_flight_guard(&self) -> InFlightGuard {
+        self.in_flight_requests.inc();
+        InFlightGuard {
+            gauge: self.in_flight_requests.clone(),
+        }
+    }
+
+    /// Wraps a body so every data frame that passes through it is added to
+    /// `counter`, without buffering the body itself.
+    pub fn counted_body(body: ProxyBody, counter: IntCounter) -> ProxyBody {
+        use http_body_util::BodyExt;
+        body.map_frame(move |frame| {
+            if let Some(data) = frame.data_ref() {
+                counter.inc_by(data.len() as u64);
+            }
+            frame
+        })
+        .boxed()
+    }
+
+    pub fn bytes_in_counter(&self) -> IntCounter {
+        self.bytes_in_total.clone()
+    }
+
+    pub fn bytes_out_counter(&self) -> IntCounter {
+        self.bytes_out_total.clone()
+    }
+
+    /// Renders all registered metrics in Prometheus text exposition format.
+    pub fn encode(&self) -> Vec<u8> {
+        let encoder = TextEncoder::new();
+        let metric_families = self.registry.gather();
+        let mut buffer = Vec::new();
+        encoder
+            .encode(&metric_families, &mut buffer)
+            .expect("encoding registered metrics cannot fail");
+        buffer
+    }
+}
+
+pub struct InFlightGuard {
+    gauge: IntGauge,
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.gauge.dec();
+    }
+}
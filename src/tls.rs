@@ -0,0 +1,134 @@
+//! TLS trust configuration for the outbound S3 client.
+//!
+//! Defaults to the platform trust store (via `rustls-native-certs`), with an
+//! optional custom CA bundle for private S3 gateways. Certificate
+//! verification can only be disabled via the explicit `TLS_INSECURE=true`
+//! opt-in, which logs a loud warning at startup.
+
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::sync::Arc;
+use tracing::{error, warn};
+
+/// Accepts any server certificate. Only ever engaged when `TLS_INSECURE=true`
+/// is set explicitly; never the default.
+#[derive(Debug)]
+struct NoVerifier(rustls::crypto::CryptoProvider);
+
+impl ServerCertVerifier for NoVerifier {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+        .map(|_| HandshakeSignatureValid::assertion())
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+        .map(|_| HandshakeSignatureValid::assertion())
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+/// Builds the `rustls::ClientConfig` used by the outbound HTTPS client.
+///
+/// Reads `TLS_INSECURE` (default `false`) and `TLS_CA_BUNDLE` (optional path
+/// to a PEM file of trusted CA certificates) from the environment.
+pub fn build_client_config() -> ClientConfig {
+    let provider = rustls::crypto::ring::default_provider();
+
+    let insecure = std::env::var("TLS_INSECURE")
+        .map(|v| v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false);
+
+    if insecure {
+        warn!(
+            "TLS_INSECURE=true: certificate verification is DISABLED for the upstream S3 \
+             connection. This must never be used in production."
+        );
+        return ClientConfig::builder()
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(NoVerifier(provider)))
+            .with_no_client_auth();
+    }
+
+    let roots = match std::env::var("TLS_CA_BUNDLE") {
+        Ok(path) => load_custom_ca_bundle(Path::new(&path)),
+        Err(_) => load_native_roots(),
+    };
+
+    ClientConfig::builder()
+        .with_root_certificates(roots)
+        .with_no_client_auth()
+}
+
+fn load_native_roots() -> RootCertStore {
+    let mut roots = RootCertStore::empty();
+    let result = rustls_native_certs::load_native_certs();
+    for cert in result.certs {
+        if let Err(e) = roots.add(cert) {
+            warn!("Skipping unparsable platform root certificate: {}", e);
+        }
+    }
+    for e in result.errors {
+        error!("Error loading platform trust store: {}", e);
+    }
+    roots
+}
+
+fn load_custom_ca_bundle(path: &Path) -> RootCertStore {
+    let mut roots = RootCertStore::empty();
+    let file = File::open(path)
+        .unwrap_or_else(|e| panic!("Failed to open TLS_CA_BUNDLE at {}: {}", path.display(), e));
+    let mut reader = BufReader::new(file);
+
+    let mut loaded = 0;
+    for cert in rustls_pemfile::certs(&mut reader) {
+        let cert = cert.unwrap_or_else(|e| {
+            panic!("Failed to parse TLS_CA_BUNDLE at {}: {}", path.display(), e)
+        });
+        match roots.add(cert) {
+            Ok(()) => loaded += 1,
+            Err(e) => warn!("Skipping unparsable CA certificate in {}: {}", path.display(), e),
+        }
+    }
+
+    tracing::info!("Loaded {} CA certificate(s) from {}", loaded, path.display());
+    roots
+}
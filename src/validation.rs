@@ -0,0 +1,182 @@
+//! S3 request validation: well-formed bucket/key paths, an allow/deny list
+//! of bucket names, and a method allow-list, so the proxy can act as a
+//! policy enforcement point instead of a permissive pass-through.
+
+use hyper::{Method, Request};
+use std::collections::HashSet;
+use std::env;
+
+/// Why a request was rejected, so the caller can pick the right status code.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ValidationError {
+    /// The path doesn't resolve to a well-formed bucket (and optional key).
+    MalformedPath,
+    /// The method isn't on the configured allow-list.
+    MethodNotAllowed,
+    /// The bucket is explicitly denied, or an allow-list is set and the
+    /// bucket isn't on it.
+    BucketNotAllowed,
+}
+
+/// Policy loaded once at startup from the environment.
+pub struct ValidationConfig {
+    allowed_methods: HashSet<Method>,
+    allowed_buckets: Option<HashSet<String>>,
+    denied_buckets: HashSet<String>,
+}
+
+impl ValidationConfig {
+    /// Reads `S3_PROXY_ALLOWED_METHODS` (comma-separated, defaults to
+    /// `GET,HEAD,PUT,POST,DELETE`), `S3_PROXY_ALLOWED_BUCKETS` (comma-separated
+    /// allow-list; unset means "any bucket not denied"), and
+    /// `S3_PROXY_DENIED_BUCKETS` (comma-separated deny-list).
+    pub fn from_env() -> Self {
+        let allowed_methods = env::var("S3_PROXY_ALLOWED_METHODS")
+            .ok()
+            .map(|v| parse_methods(&v))
+            .unwrap_or_else(default_allowed_methods);
+
+        let allowed_buckets = env::var("S3_PROXY_ALLOWED_BUCKETS")
+            .ok()
+            .map(|v| parse_bucket_list(&v));
+
+        let denied_buckets = env::var("S3_PROXY_DENIED_BUCKETS")
+            .ok()
+            .map(|v| parse_bucket_list(&v))
+            .unwrap_or_default();
+
+        Self {
+            allowed_methods,
+            allowed_buckets,
+            denied_buckets,
+        }
+    }
+}
+
+fn default_allowed_methods() -> HashSet<Method> {
+    [
+        Method::GET,
+        Method::HEAD,
+        Method::PUT,
+        Method::POST,
+        Method::DELETE,
+    ]
+    .into_iter()
+    .collect()
+}
+
+fn parse_methods(raw: &str) -> HashSet<Method> {
+    raw.split(',')
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .filter_map(|s| s.parse::<Method>().ok())
+        .collect()
+}
+
+fn parse_bucket_list(raw: &str) -> HashSet<String> {
+    raw.split(',')
+        .map(|s| s.trim().to_ascii_lowercase())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// Validates an incoming request against the configured policy.
+pub fn validate<B>(req: &Request<B>, config: &ValidationConfig) -> Result<(), ValidationError> {
+    if !config.allowed_methods.contains(req.method()) {
+        return Err(ValidationError::MethodNotAllowed);
+    }
+
+    let bucket = parse_bucket(req.uri().path()).ok_or(ValidationError::MalformedPath)?;
+
+    if !is_valid_bucket_name(&bucket) {
+        return Err(ValidationError::MalformedPath);
+    }
+
+    if config.denied_buckets.contains(&bucket) {
+        return Err(ValidationError::BucketNotAllowed);
+    }
+    if let Some(allowed) = &config.allowed_buckets {
+        if !allowed.contains(&bucket) {
+            return Err(ValidationError::BucketNotAllowed);
+        }
+    }
+
+    Ok(())
+}
+
+/// Extracts the bucket name from a path-style S3 request path (`/bucket/key...`).
+fn parse_bucket(path: &str) -> Option<String> {
+    let trimmed = path.strip_prefix('/').unwrap_or(path);
+    let bucket = trimmed.split('/').next().unwrap_or("");
+    if bucket.is_empty() {
+        None
+    } else {
+        Some(bucket.to_string())
+    }
+}
+
+/// Validates a bucket name against the S3 bucket naming rules: 3-63
+/// characters, lowercase letters/digits/hyphens/dots, no consecutive dots,
+/// and not formatted as an IPv4 address.
+fn is_valid_bucket_name(name: &str) -> bool {
+    if name.len() < 3 || name.len() > 63 {
+        return false;
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '.')
+    {
+        return false;
+    }
+    if !name.chars().next().unwrap().is_ascii_alphanumeric()
+        || !name.chars().last().unwrap().is_ascii_alphanumeric()
+    {
+        return false;
+    }
+    if name.contains("..") {
+        return false;
+    }
+    if is_ipv4_literal(name) {
+        return false;
+    }
+    true
+}
+
+fn is_ipv4_literal(name: &str) -> bool {
+    let parts: Vec<&str> = name.split('.').collect();
+    parts.len() == 4 && parts.iter().all(|p| !p.is_empty() && p.parse::<u8>().is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_well_formed_bucket_names() {
+        assert!(is_valid_bucket_name("my-bucket"));
+        assert!(is_valid_bucket_name("my.bucket.123"));
+    }
+
+    #[test]
+    fn rejects_too_short_or_too_long_names() {
+        assert!(!is_valid_bucket_name("ab"));
+        assert!(!is_valid_bucket_name(&"a".repeat(64)));
+    }
+
+    #[test]
+    fn rejects_uppercase_and_consecutive_dots() {
+        assert!(!is_valid_bucket_name("My-Bucket"));
+        assert!(!is_valid_bucket_name("my..bucket"));
+    }
+
+    #[test]
+    fn rejects_ip_address_formatted_names() {
+        assert!(!is_valid_bucket_name("192.168.1.1"));
+    }
+
+    #[test]
+    fn parse_bucket_extracts_first_path_segment() {
+        assert_eq!(parse_bucket("/my-bucket/key/path").as_deref(), Some("my-bucket"));
+        assert_eq!(parse_bucket("/"), None);
+    }
+}